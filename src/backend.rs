@@ -0,0 +1,46 @@
+//! Abstracts over the different ways a display's brightness can be read and
+//! written, so the same discovery/fade/restore logic in `main` can drive
+//! both internal panels and external monitors.
+
+use crate::Brightness;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+pub(crate) trait Backend: Send {
+    fn get(&mut self) -> Result<Brightness>;
+    fn get_max(&self) -> Brightness;
+    fn set(&mut self, value: Brightness) -> Result<()>;
+}
+
+/// Reads and writes brightness through `/sys/class/backlight/<device>/*`.
+pub(crate) struct SysfsBackend {
+    brightness_file: PathBuf,
+    actual_brightness_file: PathBuf,
+    max: Brightness,
+}
+
+impl SysfsBackend {
+    pub(crate) fn new(dir: &Path) -> Result<Self> {
+        let max = Brightness::from_file(dir.join("max_brightness"))?;
+        Ok(SysfsBackend {
+            brightness_file: dir.join("brightness"),
+            actual_brightness_file: dir.join("actual_brightness"),
+            max,
+        })
+    }
+}
+
+impl Backend for SysfsBackend {
+    fn get(&mut self) -> Result<Brightness> {
+        Brightness::from_file(&self.actual_brightness_file)
+    }
+
+    fn get_max(&self) -> Brightness {
+        self.max
+    }
+
+    fn set(&mut self, value: Brightness) -> Result<()> {
+        std::fs::write(&self.brightness_file, value.to_string())
+            .with_context(|| format!("Failed to write brightness to {:?}", self.brightness_file))
+    }
+}
@@ -0,0 +1,177 @@
+//! Per-device brightness profiles loaded from `$XDG_CONFIG_HOME/dimmer/config`.
+//!
+//! The file is a small `[section]` / `key = value` format:
+//!
+//! ```text
+//! [default]
+//! duration = 300ms
+//!
+//! [intel_backlight]
+//! target = 40%
+//!
+//! [ddcci1]
+//! target = 70%
+//! easing = linear
+//! ```
+//!
+//! The `[default]` section applies to any device with no section of its own;
+//! everything else is matched against the device's sysfs/I2C basename.
+
+use crate::fade::Easing;
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const DEFAULT_SECTION: &str = "default";
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct DeviceProfile {
+    pub(crate) target: Option<String>,
+    pub(crate) duration: Option<String>,
+    pub(crate) easing: Option<Easing>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Config {
+    default: Option<DeviceProfile>,
+    devices: HashMap<String, DeviceProfile>,
+}
+
+impl Config {
+    /// Loads the config file, or an empty `Config` if it doesn't exist.
+    pub(crate) fn load() -> Result<Config> {
+        let path = config_file_path();
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                parse(&contents).with_context(|| format!("Failed to parse config file {path:?}"))
+            }
+            Err(_) => Ok(Config::default()),
+        }
+    }
+
+    /// Looks up a device's profile by name, falling back to `[default]`.
+    pub(crate) fn profile_for(&self, device: &str) -> Option<&DeviceProfile> {
+        self.devices.get(device).or(self.default.as_ref())
+    }
+}
+
+fn config_file_path() -> PathBuf {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+            home.join(".config")
+        });
+    base.join("dimmer").join("config")
+}
+
+/// Parses a `[section]` header line, returning the section name.
+fn section_header(line: &str) -> Option<&str> {
+    line.strip_prefix('[')?.strip_suffix(']')
+}
+
+/// Parses a `key = value` line.
+fn key_value(line: &str) -> Result<(&str, &str)> {
+    let (key, value) = line
+        .split_once('=')
+        .with_context(|| format!("Expected `key = value`, got `{line}`"))?;
+    Ok((key.trim(), value.trim()))
+}
+
+fn parse(input: &str) -> Result<Config> {
+    let mut config = Config::default();
+    let mut current_section: Option<String> = None;
+
+    for raw_line in input.lines() {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = section_header(line) {
+            current_section = Some(name.to_owned());
+            continue;
+        }
+
+        let Some(section) = current_section.as_deref() else {
+            bail!("Config entry `{line}` outside of any [section]");
+        };
+        let (key, value) = key_value(line)?;
+        let profile = if section == DEFAULT_SECTION {
+            config.default.get_or_insert_with(DeviceProfile::default)
+        } else {
+            config.devices.entry(section.to_owned()).or_default()
+        };
+
+        match key {
+            "target" => profile.target = Some(value.to_owned()),
+            "duration" => profile.duration = Some(value.to_owned()),
+            "easing" => profile.easing = Some(value.parse()?),
+            other => bail!("Unknown config key `{other}` in section [{section}]"),
+        }
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_default_and_device_sections() {
+        let config = parse(
+            "
+            [default]
+            duration = 300ms
+
+            [intel_backlight]
+            target = 40%
+
+            [ddcci1]
+            target = 70%
+            easing = linear
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.profile_for("intel_backlight").unwrap().target.as_deref(),
+            Some("40%")
+        );
+        assert_eq!(
+            config.profile_for("ddcci1").unwrap().easing,
+            Some(Easing::Linear)
+        );
+        // A device with no section of its own falls back to [default].
+        assert_eq!(
+            config.profile_for("unknown_device").unwrap().duration.as_deref(),
+            Some("300ms")
+        );
+    }
+
+    #[test]
+    fn ignores_blank_lines_and_comments() {
+        let config = parse("# comment\n\n[default]\n# another comment\ntarget = 50%\n").unwrap();
+        assert_eq!(
+            config.profile_for("anything").unwrap().target.as_deref(),
+            Some("50%")
+        );
+    }
+
+    #[test]
+    fn rejects_entries_outside_a_section() {
+        assert!(parse("target = 50%").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(parse("[default]\nbogus = 1").is_err());
+    }
+
+    #[test]
+    fn profile_for_returns_none_without_a_default_section() {
+        let config = parse("[intel_backlight]\ntarget = 40%").unwrap();
+        assert!(config.profile_for("other_device").is_none());
+    }
+}
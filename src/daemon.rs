@@ -0,0 +1,102 @@
+//! `--daemon` mode: watches AC/battery state and fades every backlight to a
+//! configured target whenever the power source changes.
+
+use crate::fade::{self, Easing};
+use crate::Brightness;
+use anyhow::Result;
+use glob::glob;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum PowerState {
+    Charging,
+    Discharging,
+}
+
+pub(crate) struct DaemonConfig {
+    pub(crate) battery_target: String,
+    pub(crate) ac_target: String,
+    pub(crate) low_battery_threshold: Option<u64>,
+    pub(crate) low_battery_target: Option<String>,
+    pub(crate) poll_interval: Duration,
+}
+
+/// Runs forever, polling power state and fading to the matching target each
+/// time it changes.
+pub(crate) fn run(config: DaemonConfig, fade_duration: Duration, easing: Easing) -> Result<()> {
+    let mut last_applied: Option<String> = None;
+
+    loop {
+        let state = read_power_state();
+        let capacity = read_battery_capacity();
+
+        let target = match (config.low_battery_threshold, &config.low_battery_target, capacity) {
+            (Some(threshold), Some(low_target), Some(capacity))
+                if state == PowerState::Discharging && capacity <= threshold =>
+            {
+                low_target.clone()
+            }
+            _ => match state {
+                PowerState::Charging => config.ac_target.clone(),
+                PowerState::Discharging => config.battery_target.clone(),
+            },
+        };
+
+        if last_applied.as_deref() != Some(target.as_str()) {
+            apply_target(&target, fade_duration, easing)?;
+            last_applied = Some(target);
+        }
+
+        thread::sleep(config.poll_interval);
+    }
+}
+
+/// Fades every discovered device to `target_spec` (an absolute value or
+/// percentage; relative `+`/`-` deltas are not accepted here).
+fn apply_target(target_spec: &str, duration: Duration, easing: Easing) -> Result<()> {
+    let mut handles = Vec::new();
+    for (_, mut backend) in crate::discover_devices()? {
+        let stored = backend.get()?;
+        let maximum = backend.get_max();
+        let target = Brightness::parse_with_percentage(target_spec, maximum)?;
+        let target = if target > maximum { maximum } else { target };
+
+        handles.push(thread::spawn(move || -> Result<()> {
+            let fade = fade::Fade::new(stored, target, duration, easing);
+            fade.run(|brightness| backend.set(brightness))
+        }));
+    }
+    for handle in handles {
+        handle
+            .join()
+            .map_err(|_| anyhow::anyhow!("Fade thread panicked"))??;
+    }
+    Ok(())
+}
+
+/// Reads `/sys/class/power_supply/*/status` in glob order; the first supply
+/// with a recognised status decides the result. If none reports one, assumes
+/// AC/charging.
+fn read_power_state() -> PowerState {
+    let entries = glob("/sys/class/power_supply/*/status").expect("Failed to read glob pattern");
+    for entry in entries.flatten() {
+        let Ok(status) = std::fs::read_to_string(&entry) else {
+            continue;
+        };
+        match status.trim() {
+            "Discharging" => return PowerState::Discharging,
+            "Charging" | "Full" | "Not charging" => return PowerState::Charging,
+            _ => continue,
+        }
+    }
+    PowerState::Charging
+}
+
+/// Reads `/sys/class/power_supply/BAT*/capacity` as a percentage, if present.
+fn read_battery_capacity() -> Option<u64> {
+    let entries = glob("/sys/class/power_supply/BAT*/capacity").ok()?;
+    entries
+        .flatten()
+        .find_map(|path| std::fs::read_to_string(path).ok()?.trim().parse().ok())
+}
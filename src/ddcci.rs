@@ -0,0 +1,93 @@
+//! DDC/CI backend for external monitors, talking to the monitor's on-screen
+//! controller over I²C using VCP feature code 0x10 (luminance).
+
+use crate::backend::Backend;
+use crate::Brightness;
+use anyhow::{bail, Context, Result};
+use i2cdev::core::I2CDevice;
+use i2cdev::linux::LinuxI2CDevice;
+use std::path::Path;
+use std::time::Duration;
+
+/// I2C slave address DDC/CI monitors respond on.
+const DDC_ADDR: u16 = 0x37;
+/// Virtual host address used as the DDC/CI source address.
+const HOST_ADDR: u8 = 0x51;
+/// VCP feature code for luminance (brightness).
+const VCP_LUMINANCE: u8 = 0x10;
+/// DDC/CI requires the monitor a short moment to prepare its reply.
+const REPLY_DELAY: Duration = Duration::from_millis(40);
+
+pub(crate) struct DdcciBackend {
+    device: LinuxI2CDevice,
+    max: Brightness,
+}
+
+impl DdcciBackend {
+    pub(crate) fn new(bus: &Path) -> Result<Self> {
+        let mut device = LinuxI2CDevice::new(bus, DDC_ADDR)
+            .with_context(|| format!("Failed to open I2C bus {bus:?}"))?;
+        let (_, max) = get_vcp_feature(&mut device, VCP_LUMINANCE)?;
+        Ok(DdcciBackend { device, max })
+    }
+}
+
+impl Backend for DdcciBackend {
+    fn get(&mut self) -> Result<Brightness> {
+        let (current, _) = get_vcp_feature(&mut self.device, VCP_LUMINANCE)?;
+        Ok(current)
+    }
+
+    fn get_max(&self) -> Brightness {
+        self.max
+    }
+
+    fn set(&mut self, value: Brightness) -> Result<()> {
+        set_vcp_feature(&mut self.device, VCP_LUMINANCE, value)
+    }
+}
+
+/// XORs the destination address into the frame, per the DDC/CI checksum rule.
+fn checksum(dest_addr: u8, frame: &[u8]) -> u8 {
+    frame.iter().fold(dest_addr, |acc, byte| acc ^ byte)
+}
+
+/// Sends a "Get VCP Feature" request and returns `(current, max)`.
+fn get_vcp_feature(device: &mut LinuxI2CDevice, vcp_code: u8) -> Result<(Brightness, Brightness)> {
+    let mut request = vec![HOST_ADDR, 0x82, 0x01, vcp_code];
+    request.push(checksum((DDC_ADDR as u8) << 1, &request));
+    device
+        .write(&request)
+        .context("Failed to send Get VCP Feature request")?;
+
+    std::thread::sleep(REPLY_DELAY);
+
+    let mut reply = [0u8; 11];
+    device
+        .read(&mut reply)
+        .context("Failed to read Get VCP Feature reply")?;
+    if reply[2] != 0x02 {
+        bail!("Unexpected DDC/CI reply opcode: {:#x}", reply[2]);
+    }
+
+    let max = u16::from_be_bytes([reply[6], reply[7]]);
+    let current = u16::from_be_bytes([reply[8], reply[9]]);
+    Ok((Brightness(current as u64), Brightness(max as u64)))
+}
+
+/// Sends a "Set VCP Feature" request with the given luminance value.
+fn set_vcp_feature(device: &mut LinuxI2CDevice, vcp_code: u8, value: Brightness) -> Result<()> {
+    let value = value.0 as u16;
+    let mut request = vec![
+        HOST_ADDR,
+        0x84,
+        0x03,
+        vcp_code,
+        (value >> 8) as u8,
+        (value & 0xff) as u8,
+    ];
+    request.push(checksum((DDC_ADDR as u8) << 1, &request));
+    device
+        .write(&request)
+        .context("Failed to send Set VCP Feature request")
+}
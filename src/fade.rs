@@ -0,0 +1,160 @@
+//! Smooth, configurable transitions between two brightness values.
+
+use crate::Brightness;
+use anyhow::Result;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Frames per second used to drive a fade, independent of its total duration.
+const FRAME_RATE: f64 = 60.0;
+
+/// Fallback fade duration when neither the CLI nor the config file set one.
+pub(crate) const DEFAULT_DURATION_SPEC: &str = "300ms";
+/// Fallback easing curve when neither the CLI nor the config file set one.
+pub(crate) const DEFAULT_EASING: Easing = Easing::EaseInOut;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    fn apply(self, t: f64) -> f64 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Easing {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "linear" => Ok(Easing::Linear),
+            "ease-in" => Ok(Easing::EaseIn),
+            "ease-out" => Ok(Easing::EaseOut),
+            "ease-in-out" => Ok(Easing::EaseInOut),
+            other => Err(anyhow::anyhow!("Unknown easing mode: {other}")),
+        }
+    }
+}
+
+/// Parses a duration given as e.g. `300ms`, `1.5s`, or a bare number of
+/// milliseconds.
+pub(crate) fn parse_duration(input: &str) -> Result<Duration> {
+    let seconds = if let Some(ms) = input.strip_suffix("ms") {
+        ms.parse::<f64>()? / 1000.0
+    } else if let Some(s) = input.strip_suffix('s') {
+        s.parse::<f64>()?
+    } else {
+        input.parse::<f64>()? / 1000.0
+    };
+
+    if !seconds.is_finite() || seconds < 0.0 {
+        anyhow::bail!("Invalid duration `{input}`: must be a finite, non-negative number");
+    }
+
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// A fade from `start` to `target` over `duration`, shaped by `easing`.
+pub(crate) struct Fade {
+    start: Brightness,
+    target: Brightness,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Fade {
+    pub(crate) fn new(start: Brightness, target: Brightness, duration: Duration, easing: Easing) -> Self {
+        Fade {
+            start,
+            target,
+            duration,
+            easing,
+        }
+    }
+
+    /// Steps through every frame of the fade, calling `write` with each
+    /// intermediate brightness and sleeping one frame interval in between.
+    pub(crate) fn run(&self, mut write: impl FnMut(Brightness) -> Result<()>) -> Result<()> {
+        let frame_interval = Duration::from_secs_f64(1.0 / FRAME_RATE);
+        let frames = ((self.duration.as_secs_f64() * FRAME_RATE).round() as u64).max(1);
+
+        for i in 0..=frames {
+            let t = i as f64 / frames as f64;
+            let eased = self.easing.apply(t);
+            let value = self.start.0 as f64 + (self.target.0 as f64 - self.start.0 as f64) * eased;
+            write(Brightness(value.round() as u64))?;
+            if i < frames {
+                std::thread::sleep(frame_interval);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx_eq(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{a} != {b}");
+    }
+
+    #[test]
+    fn easing_curves_start_at_0_and_end_at_1() {
+        for easing in [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut] {
+            approx_eq(easing.apply(0.0), 0.0);
+            approx_eq(easing.apply(1.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn linear_is_identity() {
+        approx_eq(Easing::Linear.apply(0.25), 0.25);
+        approx_eq(Easing::Linear.apply(0.75), 0.75);
+    }
+
+    #[test]
+    fn ease_in_out_is_symmetric_around_the_midpoint() {
+        approx_eq(Easing::EaseInOut.apply(0.5), 0.5);
+        approx_eq(Easing::EaseInOut.apply(0.25) + Easing::EaseInOut.apply(0.75), 1.0);
+    }
+
+    #[test]
+    fn parse_duration_accepts_ms_and_s_suffixes() {
+        assert_eq!(parse_duration("300ms").unwrap(), Duration::from_millis(300));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("300").unwrap(), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn parse_duration_rejects_negative_and_non_finite_values() {
+        assert!(parse_duration("-1s").is_err());
+        assert!(parse_duration("-300ms").is_err());
+        assert!(parse_duration("NaNms").is_err());
+    }
+
+    #[test]
+    fn easing_from_str_round_trips_known_names() {
+        assert_eq!("linear".parse::<Easing>().unwrap(), Easing::Linear);
+        assert_eq!("ease-in".parse::<Easing>().unwrap(), Easing::EaseIn);
+        assert_eq!("ease-out".parse::<Easing>().unwrap(), Easing::EaseOut);
+        assert_eq!("ease-in-out".parse::<Easing>().unwrap(), Easing::EaseInOut);
+        assert!("bogus".parse::<Easing>().is_err());
+    }
+}
@@ -1,13 +1,20 @@
 use anyhow::{Context, Result};
 use glob::{glob, Paths};
-use std::fs::File;
-use std::io::Write;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
 use std::thread;
 use structopt::StructOpt;
 use thiserror::Error;
 
+use backend::{Backend, SysfsBackend};
+use ddcci::DdcciBackend;
+
+mod backend;
+mod config;
+mod daemon;
+mod ddcci;
+mod fade;
+mod state;
+
 #[derive(Error, Debug)]
 enum DimmerError {
     #[error("Invalid percentage given by user")]
@@ -17,7 +24,7 @@ enum DimmerError {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
-struct Brightness(u64);
+pub(crate) struct Brightness(pub(crate) u64);
 
 impl std::fmt::Display for Brightness {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -34,7 +41,7 @@ impl std::str::FromStr for Brightness {
 }
 
 impl Brightness {
-    fn parse_with_percentage(input: &str, max: Brightness) -> Result<Brightness> {
+    pub(crate) fn parse_with_percentage(input: &str, max: Brightness) -> Result<Brightness> {
         match input.strip_suffix('%') {
             Some(percentage) => {
                 let percentage = percentage.parse::<u64>()?;
@@ -49,7 +56,22 @@ impl Brightness {
         }
     }
 
-    fn from_file<P: AsRef<Path>>(path: P) -> Result<Brightness> {
+    /// Parses a CLI target that may be absolute (`50`, `50%`) or relative to
+    /// `stored` (`+10%`, `-20`, `+64`). The result is not yet clamped to
+    /// `max`; callers still need the usual upper-bound check.
+    fn parse_adjustment(input: &str, stored: Brightness, max: Brightness) -> Result<Brightness> {
+        if let Some(delta) = input.strip_prefix('+') {
+            let delta = Self::parse_with_percentage(delta, max)?;
+            Ok(Brightness(stored.0.saturating_add(delta.0)))
+        } else if let Some(delta) = input.strip_prefix('-') {
+            let delta = Self::parse_with_percentage(delta, max)?;
+            Ok(Brightness(stored.0.saturating_sub(delta.0)))
+        } else {
+            Self::parse_with_percentage(input, max)
+        }
+    }
+
+    pub(crate) fn from_file<P: AsRef<Path>>(path: P) -> Result<Brightness> {
         let path = path.as_ref();
         let res = std::fs::read_to_string(path)
             .context("Failed to read {path}")?
@@ -61,78 +83,217 @@ impl Brightness {
 }
 
 #[derive(Debug, StructOpt)]
+#[structopt(setting = structopt::clap::AppSettings::AllowLeadingHyphen)]
 struct Opt {
     #[structopt(long, short)]
     restore: bool,
+
+    /// Absolute value, percentage (50%), or relative delta (+10%, -20, +64)
+    #[structopt(allow_hyphen_values = true)]
+    target: Option<String>,
+
+    /// Total fade duration, e.g. "300ms" or "1s". Overrides the config file.
+    #[structopt(long)]
+    duration: Option<String>,
+
+    /// Fade easing curve: linear, ease-in, ease-out, ease-in-out. Overrides the config file.
+    #[structopt(long)]
+    easing: Option<fade::Easing>,
+
+    /// Run as a daemon that fades to a battery/AC target on power-state changes
+    #[structopt(long)]
+    daemon: bool,
+
+    /// Target to fade to while on battery power, in daemon mode
+    #[structopt(long, default_value = "40%")]
+    battery_target: String,
+
+    /// Target to fade to while on AC power, in daemon mode
+    #[structopt(long, default_value = "100%")]
+    ac_target: String,
+
+    /// Battery percentage at or below which to use --low-battery-target instead, while discharging
+    #[structopt(long)]
+    low_battery_threshold: Option<u64>,
+
+    /// Target to fade to once battery capacity drops to --low-battery-threshold
+    #[structopt(long)]
+    low_battery_target: Option<String>,
+
+    /// How often to poll power state, in daemon mode
+    #[structopt(long, default_value = "30s")]
+    poll_interval: String,
 }
 
 const SYS_BACKLIGHT_PREFIX: &str = "/sys/class/backlight";
+const I2C_BUS_GLOB: &str = "/dev/i2c-*";
 
-fn main() -> Result<()> {
-    let opt = Opt::from_args();
+/// Discovers every backlight this tool knows how to drive: internal panels
+/// under sysfs, and external monitors that answer DDC/CI over I2C.
+pub(crate) fn discover_devices() -> Result<Vec<(String, Box<dyn Backend>)>> {
+    let mut devices: Vec<(String, Box<dyn Backend>)> = Vec::new();
 
     let glob_path = format!("{SYS_BACKLIGHT_PREFIX}/*/brightness");
-    let glob: Paths = glob(&glob_path).expect("Failed to read glob pattern");
+    let sysfs_glob: Paths = glob(&glob_path).expect("Failed to read glob pattern");
+    // A matched path can still fail to read while the walk is in progress
+    // (directory removed mid-enumeration, permission denied); skip those
+    // instead of aborting discovery entirely, same as the I2C loop below.
+    for entry in sysfs_glob.flatten() {
+        let dir = entry.parent().unwrap().to_path_buf();
+        let name = dir.file_name().unwrap().to_str().unwrap().to_owned();
+        match SysfsBackend::new(&dir) {
+            Ok(backend) => devices.push((name, Box::new(backend))),
+            Err(e) => eprintln!("Skipping backlight {name}: {e:#}"),
+        }
+    }
 
-    let mut thread = None;
-    for i in glob {
-        let parent = i.unwrap().parent().unwrap().to_str().unwrap().to_owned();
+    let i2c_glob: Paths = glob(I2C_BUS_GLOB).expect("Failed to read glob pattern");
+    for bus in i2c_glob.flatten() {
+        let name = bus.file_name().unwrap().to_str().unwrap().to_owned();
+        // Most I2C buses aren't attached to a DDC/CI monitor; skip the ones
+        // that don't answer rather than aborting discovery entirely.
+        if let Ok(backend) = DdcciBackend::new(&bus) {
+            devices.push((name, Box::new(backend)));
+        }
+    }
 
-        let q = parent.clone() + "/brightness";
-        let w = parent.clone() + "/actual_brightness";
-        let e = parent.clone() + "/max_brightness";
-        let brightness_file = Path::new(&q);
-        let current_brightness_file = Path::new(&w);
-        let max_brightness_file = Path::new(&e);
+    Ok(devices)
+}
 
-        let stored: Brightness = Brightness::from_file(&current_brightness_file)?;
-        let maximum: Brightness = Brightness::from_file(&max_brightness_file)?;
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
 
-        let target: Brightness = if opt.restore {
-            if parent == "{SYS_BACKLIGHT_PREFIX}/ddcci9" {
-                Brightness::parse_with_percentage("70", maximum)?
-            } else {
-                Brightness::parse_with_percentage("100", maximum)?
-            }
-        } else {
-            Brightness::parse_with_percentage("0", maximum)?
+    if opt.daemon {
+        let duration = fade::parse_duration(
+            opt.duration.as_deref().unwrap_or(fade::DEFAULT_DURATION_SPEC),
+        )?;
+        let easing = opt.easing.unwrap_or(fade::DEFAULT_EASING);
+        let config = daemon::DaemonConfig {
+            battery_target: opt.battery_target,
+            ac_target: opt.ac_target,
+            low_battery_threshold: opt.low_battery_threshold,
+            low_battery_target: opt.low_battery_target,
+            poll_interval: fade::parse_duration(&opt.poll_interval)?,
         };
+        return daemon::run(config, duration, easing);
+    }
 
-        let target = if target > maximum { maximum } else { target };
-
-        let step_size = 4;
-
-        let output = Arc::new(Mutex::new(File::create(&brightness_file)?));
-        let mut brightness = stored;
+    let config = config::Config::load()?;
+    let mut saved_state = state::load();
 
-        let file = Arc::clone(&output);
+    let mut handles = Vec::new();
+    for (name, mut backend) in discover_devices()? {
+        let stored = backend.get()?;
+        let maximum = backend.get_max();
+        let profile = config.profile_for(&name);
 
-        thread = Some(thread::spawn(move || loop {
-            if target.0 == brightness.0 {
-                break;
+        let target: Brightness = if opt.restore {
+            saved_state.get(&name).copied().unwrap_or(maximum)
+        } else {
+            // Only record a new restore point when the device isn't already
+            // dimmed below its last one, so repeated dims don't clobber the
+            // true pre-dim brightness with an already-dimmed value.
+            let already_dimmed = saved_state.get(&name).is_some_and(|saved| stored < *saved);
+            if !already_dimmed {
+                saved_state.insert(name.clone(), stored);
             }
-            if target.0 == 0 {
-                if brightness.0 < step_size {
-                    brightness = Brightness(0);
-                } else {
-                    brightness = Brightness(brightness.0 - step_size);
-                }
-            } else if (target.0 - brightness.0) < step_size {
-                brightness = target;
+            if let Some(value) = &opt.target {
+                Brightness::parse_adjustment(value, stored, maximum)?
+            } else if let Some(profile_target) = profile.and_then(|p| p.target.as_deref()) {
+                Brightness::parse_with_percentage(profile_target, maximum)?
             } else {
-                brightness = target;
+                Brightness::parse_with_percentage("0", maximum)?
             }
+        };
+        let target = if target > maximum { maximum } else { target };
 
-            dbg!(&output);
-            let mut file = file.lock().unwrap();
-            write!(file, "{}", brightness.0).expect("Failed to write file!");
-            std::thread::sleep(std::time::Duration::from_millis(1000 / 100));
+        let duration_spec = opt
+            .duration
+            .clone()
+            .or_else(|| profile.and_then(|p| p.duration.clone()))
+            .unwrap_or_else(|| fade::DEFAULT_DURATION_SPEC.to_owned());
+        let duration = fade::parse_duration(&duration_spec)?;
+        let easing = opt
+            .easing
+            .or_else(|| profile.and_then(|p| p.easing))
+            .unwrap_or(fade::DEFAULT_EASING);
+
+        handles.push(thread::spawn(move || -> Result<()> {
+            let fade = fade::Fade::new(stored, target, duration, easing);
+            fade.run(|brightness| backend.set(brightness))
         }));
     }
 
-    if let Some(value) = thread {
-        let _ = value.join();
+    if !opt.restore {
+        state::save(&saved_state)?;
+    }
+
+    for handle in handles {
+        handle
+            .join()
+            .map_err(|_| anyhow::anyhow!("Fade thread panicked"))??;
     }
     println!("Ok!");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_with_percentage_computes_fraction_of_max() {
+        let max = Brightness(200);
+        assert_eq!(Brightness::parse_with_percentage("50%", max).unwrap().0, 100);
+        assert_eq!(Brightness::parse_with_percentage("0%", max).unwrap().0, 0);
+        assert_eq!(Brightness::parse_with_percentage("100%", max).unwrap().0, 200);
+    }
+
+    #[test]
+    fn parse_with_percentage_rejects_over_100() {
+        assert!(Brightness::parse_with_percentage("101%", Brightness(200)).is_err());
+    }
+
+    #[test]
+    fn parse_with_percentage_accepts_absolute_values() {
+        assert_eq!(Brightness::parse_with_percentage("42", Brightness(200)).unwrap().0, 42);
+    }
+
+    #[test]
+    fn parse_adjustment_applies_relative_delta() {
+        let stored = Brightness(50);
+        let max = Brightness(200);
+        assert_eq!(Brightness::parse_adjustment("+10", stored, max).unwrap().0, 60);
+        assert_eq!(Brightness::parse_adjustment("-10", stored, max).unwrap().0, 40);
+        assert_eq!(
+            Brightness::parse_adjustment("+50%", stored, max).unwrap().0,
+            150
+        );
+    }
+
+    #[test]
+    fn parse_adjustment_saturates_instead_of_underflowing() {
+        let stored = Brightness(5);
+        let max = Brightness(200);
+        assert_eq!(Brightness::parse_adjustment("-20", stored, max).unwrap().0, 0);
+    }
+
+    #[test]
+    fn parse_adjustment_falls_back_to_absolute_without_a_sign() {
+        let stored = Brightness(5);
+        let max = Brightness(200);
+        assert_eq!(Brightness::parse_adjustment("80", stored, max).unwrap().0, 80);
+    }
+
+    #[test]
+    fn cli_accepts_a_negative_relative_target() {
+        let opt = Opt::from_iter_safe(["dimmer", "-20"]).unwrap();
+        assert_eq!(opt.target.as_deref(), Some("-20"));
+    }
+
+    #[test]
+    fn cli_accepts_a_positive_relative_target() {
+        let opt = Opt::from_iter_safe(["dimmer", "+10%"]).unwrap();
+        assert_eq!(opt.target.as_deref(), Some("+10%"));
+    }
+}
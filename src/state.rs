@@ -0,0 +1,87 @@
+//! Persists each device's pre-dim brightness so `--restore` can bring the
+//! screen back to where the user left it, rather than guessing a value.
+
+use crate::Brightness;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Location of the state file, honoring `$XDG_STATE_HOME` and falling back
+/// to `/run` when it isn't set.
+fn state_file_path() -> PathBuf {
+    let base = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/run"));
+    base.join("dimmer").join("state")
+}
+
+/// Loads the saved per-device brightness map, keyed by device name (e.g.
+/// `intel_backlight`). Returns an empty map if no state has been saved yet.
+pub fn load() -> HashMap<String, Brightness> {
+    load_from(&state_file_path())
+}
+
+/// Writes the per-device brightness map back to the state file, creating its
+/// parent directory if necessary.
+pub fn save(state: &HashMap<String, Brightness>) -> Result<()> {
+    save_to(&state_file_path(), state)
+}
+
+fn load_from(path: &Path) -> HashMap<String, Brightness> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return HashMap::new();
+    };
+    contents
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .filter_map(|(device, value)| {
+            value
+                .trim()
+                .parse::<u64>()
+                .ok()
+                .map(|v| (device.to_owned(), Brightness(v)))
+        })
+        .collect()
+}
+
+fn save_to(path: &Path, state: &HashMap<String, Brightness>) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create state directory {dir:?}"))?;
+    }
+    let contents: String = state
+        .iter()
+        .map(|(device, brightness)| format!("{device}={brightness}\n"))
+        .collect();
+    std::fs::write(path, contents).with_context(|| format!("Failed to write state file {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_the_state_file() {
+        let dir = std::env::temp_dir().join(format!(
+            "dimmer-state-test-{:?}",
+            std::thread::current().id()
+        ));
+        let path = dir.join("state");
+
+        let mut state = HashMap::new();
+        state.insert("intel_backlight".to_owned(), Brightness(123));
+        state.insert("ddcci1".to_owned(), Brightness(456));
+
+        save_to(&path, &state).unwrap();
+        let loaded = load_from(&path);
+
+        assert_eq!(loaded, state);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_from_missing_file_returns_empty_map() {
+        let path = std::env::temp_dir().join("dimmer-state-test-missing/state");
+        assert!(load_from(&path).is_empty());
+    }
+}